@@ -1,73 +1,261 @@
-use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use rsa::{
     pkcs8::{DecodePrivateKey, EncodePrivateKey, EncodePublicKey},
     RsaPrivateKey, RsaPublicKey,
 };
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
 
-const ALGORITHM: Algorithm = Algorithm::RS256;
 const SECRET_ABSENT: &'_ str = "`pvt_key_secret` is required for generating token.";
 const PVT_KEY_ABSENT: &'_ str = "`encrypted_pvt_key` is required for generating token.";
 const PUB_KEY_ABSENT: &'_ str = "`pub_key` is required for verifying token.";
+const HMAC_SECRET_ABSENT: &'_ str = "`secret` is required for HMAC algorithms.";
+const EC_PVT_KEY_ABSENT: &'_ str = "`ec_pvt_key` is required for ECDSA algorithms.";
+const EC_PUB_KEY_ABSENT: &'_ str = "`ec_pub_key` is required for ECDSA algorithms.";
 
-/// This helper uses `RS256` algorithm.
+/// Supports the RSASSA-PKCS1 (`RS256`/`RS384`/`RS512`) and RSA-PSS (`PS256`/`PS384`/`PS512`)
+/// families via an encrypted PKCS#8 RSA keypair, the ECDSA family (`ES256`/`ES384`) via a PKCS#8
+/// EC keypair, and the HMAC family (`HS256`/`HS384`/`HS512`) via a shared secret. For the RSA
+/// family, key rotation is supported via `key_id`/`verification_keys`: tokens are signed with a
+/// `kid` header, and verification picks the matching key out of a keyset instead of a single key.
 /// For instantiation example, see [`tests::generate_and_verify_token`].
-#[derive(buildstructor::Builder, Debug)]
 pub struct JWTHelper {
-    // Pass-phrase that private key has been encrypted with.
-    pvt_key_secret: Option<String>,
+    algorithm: Algorithm,
     expiry_secs: usize,
     leeway: u64,
-    encrypted_pvt_key: Option<String>,
-    pub_key: Option<RsaPublicKey>,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    issuer: Option<String>,
+    audience: Vec<String>,
+    nbf_offset_secs: Option<i64>,
+    // Clock source for `exp`/`iat`/`nbf`, injectable so tests can move time without `thread::sleep`.
+    now: fn() -> DateTime<Utc>,
+    // `kid` header stamped on generated tokens, so verifiers know which rotated key to check against.
+    key_id: Option<String>,
+    // Additional candidate keys for rotation, keyed by `kid`. [`JWTHelper::validate_token`] picks
+    // the one matching the token's `kid` header, falling back to trying every configured key
+    // (`decoding_key` plus this map) when the header carries no `kid` or an unrecognised one.
+    decoding_keys: HashMap<String, DecodingKey>,
 }
 
+// Hand-written so key material (`encoding_key`/`decoding_key`/`decoding_keys`) never ends up in
+// logs, and because `jsonwebtoken`'s `EncodingKey`/`DecodingKey` don't derive `Debug`.
+impl std::fmt::Debug for JWTHelper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JWTHelper")
+            .field("algorithm", &self.algorithm)
+            .field("expiry_secs", &self.expiry_secs)
+            .field("leeway", &self.leeway)
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .field("nbf_offset_secs", &self.nbf_offset_secs)
+            .field("key_id", &self.key_id)
+            .finish_non_exhaustive()
+    }
+}
+
+#[buildstructor::buildstructor]
 impl JWTHelper {
+    /// Builds the [`EncodingKey`]/[`DecodingKey`] pair appropriate for `algorithm` once here, so
+    /// that [`JWTHelper::generate_token`] and [`JWTHelper::validate_token`] can sign/verify against
+    /// the already-built keys instead of redoing this work on every call.
+    #[builder]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        algorithm: Algorithm,
+        // Pass-phrase that `encrypted_pvt_key` has been encrypted with. Only used for the RSA family.
+        pvt_key_secret: Option<String>,
+        expiry_secs: usize,
+        leeway: u64,
+        encrypted_pvt_key: Option<String>,
+        pub_key: Option<RsaPublicKey>,
+        // Shared secret. Only used for the HMAC family.
+        secret: Option<String>,
+        // PEM-encoded PKCS#8 EC private/public keys. Only used for the ECDSA family.
+        ec_pvt_key: Option<String>,
+        ec_pub_key: Option<String>,
+        // `iss` registered claim to stamp on generated tokens and require on validation.
+        issuer: Option<String>,
+        // `aud` registered claim to stamp on generated tokens. Validation accepts a token if its
+        // `aud` shares at least one member with this list ("any-of" semantics).
+        audience: Vec<String>,
+        // Offset (in seconds, from issue time) after which a generated token becomes valid. When
+        // set, stamps an `nbf` claim of `iat + nbf_offset_secs`.
+        nbf_offset_secs: Option<i64>,
+        // Defaults to [`Utc::now`]. Override to make `exp`/`iat`/`nbf` boundaries deterministic in tests.
+        now: Option<fn() -> DateTime<Utc>>,
+        // `kid` to stamp on generated tokens' header, identifying which rotated key signed them.
+        key_id: Option<String>,
+        // Candidate public keys for verification during key rotation, keyed by `kid`. Only used for
+        // the RSA family.
+        verification_keys: HashMap<String, RsaPublicKey>,
+    ) -> Result<Self, anyhow::Error> {
+        let now = now.unwrap_or(Utc::now);
+        let (encoding_key, decoding_key) = match algorithm {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                let secret = secret.as_ref().ok_or_else(|| anyhow::anyhow!(HMAC_SECRET_ABSENT))?.as_bytes();
+                (EncodingKey::from_secret(secret), DecodingKey::from_secret(secret))
+            }
+            Algorithm::ES256 | Algorithm::ES384 => (
+                EncodingKey::from_ec_pem(
+                    ec_pvt_key.as_ref().ok_or_else(|| anyhow::anyhow!(EC_PVT_KEY_ABSENT))?.as_bytes(),
+                )?,
+                DecodingKey::from_ec_pem(
+                    ec_pub_key.as_ref().ok_or_else(|| anyhow::anyhow!(EC_PUB_KEY_ABSENT))?.as_bytes(),
+                )?,
+            ),
+            _ => {
+                let decrypted_key = <RsaPrivateKey as DecodePrivateKey>::from_pkcs8_encrypted_pem(
+                    encrypted_pvt_key.as_ref().ok_or_else(|| anyhow::anyhow!(PVT_KEY_ABSENT))?.as_ref(),
+                    <std::string::String as AsRef<[u8]>>::as_ref(
+                        pvt_key_secret.as_ref().ok_or_else(|| anyhow::anyhow!(SECRET_ABSENT))?,
+                    ),
+                )?;
+                let encoding_key = EncodingKey::from_rsa_pem(decrypted_key.to_pkcs8_pem(Default::default())?.as_bytes())?;
+
+                let decoding_key = DecodingKey::from_rsa_pem(
+                    pub_key
+                        .as_ref()
+                        .ok_or_else(|| anyhow::anyhow!(PUB_KEY_ABSENT))?
+                        .to_public_key_pem(Default::default())?
+                        .as_bytes(),
+                )?;
+
+                (encoding_key, decoding_key)
+            }
+        };
+
+        let mut decoding_keys = HashMap::with_capacity(verification_keys.len());
+        for (kid, pub_key) in &verification_keys {
+            let decoding_key = DecodingKey::from_rsa_pem(pub_key.to_public_key_pem(Default::default())?.as_bytes())?;
+            decoding_keys.insert(kid.clone(), decoding_key);
+        }
+
+        Ok(Self {
+            algorithm,
+            expiry_secs,
+            leeway,
+            encoding_key,
+            decoding_key,
+            issuer,
+            audience,
+            nbf_offset_secs,
+            now,
+            key_id,
+            decoding_keys,
+        })
+    }
+
     /// Pass in the [`subject`] to identify who the token is issued to, e.g. user_id in DB.
     /// If successful, returns signed jwt token which expires according to config set while [`JWTHelper`] instantiation.
     pub fn generate_token(&self, subject: String) -> Result<String, anyhow::Error> {
-        let header = Header::new(ALGORITHM);
+        self.generate_token_with_claims(&Claims { sub: subject })
+    }
 
-        let claims = Claims {
-            exp: (Utc::now().timestamp() + Duration::seconds(self.expiry_secs as i64).num_seconds()) as usize,
-            iat: Utc::now().timestamp() as usize,
-            sub: subject,
-        };
+    /// Same as [`JWTHelper::generate_token`], but lets callers bring their own claims type instead
+    /// of being limited to `sub`. `exp`/`iat` are injected automatically, as are `nbf`, `iss` and
+    /// `aud` when the helper is configured with them; an injected claim replaces a field of the same
+    /// name in `claims`. `claims` only needs to carry whatever else the caller wants in the payload
+    /// (roles, tenant id, etc.).
+    pub fn generate_token_with_claims<T: Serialize>(&self, claims: &T) -> Result<String, anyhow::Error> {
+        let mut header = Header::new(self.algorithm);
+        header.kid = self.key_id.clone();
 
-        let decrypted_key = <RsaPrivateKey as DecodePrivateKey>::from_pkcs8_encrypted_pem(
-            self.encrypted_pvt_key.as_ref().expect(PVT_KEY_ABSENT).as_ref(),
-            <std::string::String as AsRef<[u8]>>::as_ref(self.pvt_key_secret.as_ref().expect(SECRET_ABSENT)),
-        )?;
-        let key = &EncodingKey::from_rsa_pem(decrypted_key.to_pkcs8_pem(Default::default())?.as_bytes())?;
+        let now = (self.now)();
+        let mut claims = serde_json::to_value(claims)?;
+        let claims_map = claims
+            .as_object_mut()
+            .ok_or_else(|| anyhow::anyhow!("claims must serialize to a JSON object"))?;
+        claims_map.insert(
+            "exp".to_string(),
+            json!((now.timestamp() + Duration::seconds(self.expiry_secs as i64).num_seconds()) as usize),
+        );
+        claims_map.insert("iat".to_string(), json!(now.timestamp() as usize));
+        if let Some(nbf_offset_secs) = self.nbf_offset_secs {
+            claims_map.insert("nbf".to_string(), json!((now.timestamp() + Duration::seconds(nbf_offset_secs).num_seconds()) as usize));
+        }
+        if let Some(issuer) = &self.issuer {
+            claims_map.insert("iss".to_string(), json!(issuer));
+        }
+        if !self.audience.is_empty() {
+            claims_map.insert("aud".to_string(), json!(self.audience));
+        }
 
-        Ok(encode(&header, &claims, &key)?)
+        Ok(encode(&header, &claims, &self.encoding_key)?)
     }
 
     pub fn validate_token(&self, token: &str) -> Result<String, anyhow::Error> {
-        let mut validation = Validation::new(ALGORITHM);
-        validation.validate_exp = true;
-        validation.leeway = self.leeway;
-        let data = decode::<Claims>(
-            &token,
-            &DecodingKey::from_rsa_pem(
-                self.pub_key
-                    .as_ref()
-                    .expect(PUB_KEY_ABSENT)
-                    .to_public_key_pem(Default::default())?
-                    .as_bytes(),
-            )?,
-            &validation,
-        )?;
-
-        Ok(data.claims.sub)
+        Ok(self.validate_token_into::<Claims>(token)?.sub)
+    }
+
+    /// Same as [`JWTHelper::validate_token`], but deserializes the claims into a caller-supplied
+    /// type instead of returning just `sub`.
+    pub fn validate_token_into<T: DeserializeOwned>(&self, token: &str) -> Result<T, anyhow::Error> {
+        // `exp`/`nbf` are checked ourselves against `self.now` (see below) rather than left to
+        // jsonwebtoken, which always validates them against the real system clock and would make
+        // the injected clock a no-op for validation.
+        let mut validation = Validation::new(self.algorithm);
+        validation.validate_exp = false;
+        validation.validate_nbf = false;
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+        if !self.audience.is_empty() {
+            validation.set_audience(&self.audience);
+        }
+
+        let kid = decode_header(token)?.kid;
+        let candidate_keys: Vec<&DecodingKey> = match kid.as_deref().and_then(|kid| self.decoding_keys.get(kid)) {
+            Some(decoding_key) => vec![decoding_key],
+            None => std::iter::once(&self.decoding_key).chain(self.decoding_keys.values()).collect(),
+        };
+
+        let mut last_err = None;
+        let mut claims = None;
+        for decoding_key in candidate_keys {
+            match decode::<serde_json::Value>(&token, decoding_key, &validation) {
+                Ok(data) => {
+                    claims = Some(data.claims);
+                    break;
+                }
+                Err(err) => last_err = Some(err),
+            }
+        }
+        let claims = match claims {
+            Some(claims) => claims,
+            None => return Err(last_err.expect("at least the primary decoding key is always configured").into()),
+        };
+
+        self.validate_exp_and_nbf(&claims)?;
+
+        Ok(serde_json::from_value(claims)?)
+    }
+
+    // Mirrors jsonwebtoken's own exp/nbf comparisons, but against `self.now` instead of the real
+    // system clock, so the injected clock actually drives expiry/not-before checks.
+    fn validate_exp_and_nbf(&self, claims: &serde_json::Value) -> Result<(), anyhow::Error> {
+        let now = (self.now)().timestamp();
+        let leeway = self.leeway as i64;
+
+        if let Some(exp) = claims.get("exp").and_then(serde_json::Value::as_i64) {
+            if exp < now - leeway {
+                anyhow::bail!("ExpiredSignature");
+            }
+        }
+        if let Some(nbf) = claims.get("nbf").and_then(serde_json::Value::as_i64) {
+            if nbf > now + leeway {
+                anyhow::bail!("ImmatureSignature");
+            }
+        }
+
+        Ok(())
     }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Claims {
-    exp: usize,
-    iat: usize,
     sub: String,
 }
 
@@ -76,17 +264,41 @@ mod tests {
     use super::*;
     use rsa::pkcs8::DecodePublicKey;
     use std::process::Command;
-    use std::thread;
-    use std::time::Duration;
+    use std::sync::atomic::{AtomicI64, Ordering};
     use std::{fs, path::Path};
 
     const TEST_KEYS_DIR: &'_ str = "./test_keys";
-    const PVT_KEY_PATH: &'_ str = "./test_keys/rsa";
-    const PUB_KEY_PATH: &'_ str = "./test_keys/rsa.pub";
+    // Each test generates its own keypair under a distinct path: `rsa_keypair_pems_at`'s
+    // check-then-create isn't atomic, so tests sharing a path would race each other to generate it
+    // when run concurrently (the default).
+    const EXPIRY_PVT_KEY_PATH: &'_ str = "./test_keys/rsa_expiry";
+    const EXPIRY_PUB_KEY_PATH: &'_ str = "./test_keys/rsa_expiry.pub";
+    const NBF_PVT_KEY_PATH: &'_ str = "./test_keys/rsa_nbf";
+    const NBF_PUB_KEY_PATH: &'_ str = "./test_keys/rsa_nbf.pub";
+    const KID_PRIMARY_PVT_KEY_PATH: &'_ str = "./test_keys/rsa_kid_primary";
+    const KID_PRIMARY_PUB_KEY_PATH: &'_ str = "./test_keys/rsa_kid_primary.pub";
+    const ROTATED_PVT_KEY_PATH: &'_ str = "./test_keys/rsa_rotated";
+    const ROTATED_PUB_KEY_PATH: &'_ str = "./test_keys/rsa_rotated.pub";
     const PVT_KEY_SECRET: &'_ str = "testpassword";
 
-    #[test]
-    fn generate_and_verify_token() -> Result<(), anyhow::Error> {
+    // Offsets (in seconds) applied on top of the real clock by the `now` fns below, so tests can
+    // move time forward deterministically instead of `thread::sleep`-ing past `exp`/`nbf`
+    // boundaries. Each test gets its own static, since `now` is a bare fn pointer (no closure
+    // captures) and tests run concurrently by default.
+    static EXPIRY_TEST_CLOCK_OFFSET_SECS: AtomicI64 = AtomicI64::new(0);
+    static NBF_TEST_CLOCK_OFFSET_SECS: AtomicI64 = AtomicI64::new(0);
+
+    fn expiry_test_now() -> DateTime<Utc> {
+        Utc::now() + Duration::seconds(EXPIRY_TEST_CLOCK_OFFSET_SECS.load(Ordering::SeqCst))
+    }
+
+    fn nbf_test_now() -> DateTime<Utc> {
+        Utc::now() + Duration::seconds(NBF_TEST_CLOCK_OFFSET_SECS.load(Ordering::SeqCst))
+    }
+
+    // Generates (if missing) and reads back an RSA keypair at the given paths, returning the
+    // encrypted private key PEM and the public key PEM.
+    fn rsa_keypair_pems_at(pvt_key_path: &str, pub_key_path: &str) -> Result<(String, String), anyhow::Error> {
         // Create "test_keys" directory if it doesn't exist.
         fs::create_dir_all(TEST_KEYS_DIR)?;
         let mut cli_arg_pass = "pass:".to_string();
@@ -95,14 +307,14 @@ mod tests {
         // if rsa256 private key doesn't exist, generate it using openssl.
         // Reason for using openssl, is that in deployment setup we probably would be using
         // openssl rather than rust code.
-        if !Path::new(PVT_KEY_PATH).exists() {
+        if !Path::new(pvt_key_path).exists() {
             let pvt_key_generated = Command::new("openssl")
                 .arg("genrsa")
                 .arg("-aes256")
                 .arg("-passout")
                 .arg(&cli_arg_pass)
                 .arg("-out")
-                .arg(PVT_KEY_PATH)
+                .arg(pvt_key_path)
                 .arg("4096")
                 .spawn()?
                 .wait()?
@@ -111,16 +323,16 @@ mod tests {
         }
 
         // if rsa256 public key doesn't exist, generate it using private key file.
-        if !Path::new(PUB_KEY_PATH).exists() {
+        if !Path::new(pub_key_path).exists() {
             let pub_key_generated = Command::new("openssl")
                 .arg("rsa")
                 .arg("-in")
-                .arg(PVT_KEY_PATH)
+                .arg(pvt_key_path)
                 .arg("-passin")
                 .arg(&cli_arg_pass)
                 .arg("-pubout")
                 .arg("-out")
-                .arg(PUB_KEY_PATH)
+                .arg(pub_key_path)
                 .spawn()?
                 .wait()?
                 .success();
@@ -128,16 +340,69 @@ mod tests {
         }
 
         // Read private key and public key generated by openssl from their respective files.
-        let encrypted_pvt_key = fs::read_to_string(PVT_KEY_PATH)?;
-        let pub_key = fs::read_to_string(PUB_KEY_PATH)?;
+        Ok((fs::read_to_string(pvt_key_path)?, fs::read_to_string(pub_key_path)?))
+    }
+
+    const EC_PVT_KEY_PATH: &'_ str = "./test_keys/ec";
+    const EC_PUB_KEY_PATH: &'_ str = "./test_keys/ec.pub";
+
+    // Generates (if missing) and reads back a PKCS#8 EC keypair at the given paths. Unlike the RSA
+    // keypair, this one isn't passphrase-protected: `EncodingKey::from_ec_pem` expects an
+    // unencrypted PKCS#8 PEM.
+    fn ec_keypair_pems_at(pvt_key_path: &str, pub_key_path: &str) -> Result<(String, String), anyhow::Error> {
+        fs::create_dir_all(TEST_KEYS_DIR)?;
+
+        if !Path::new(pvt_key_path).exists() {
+            let raw_key_path = format!("{pvt_key_path}.raw");
+            let raw_key_generated = Command::new("openssl")
+                .args(["ecparam", "-name", "prime256v1", "-genkey", "-noout", "-out"])
+                .arg(&raw_key_path)
+                .spawn()?
+                .wait()?
+                .success();
+            assert!(raw_key_generated);
+
+            let pkcs8_key_generated = Command::new("openssl")
+                .args(["pkcs8", "-topk8", "-nocrypt", "-in"])
+                .arg(&raw_key_path)
+                .arg("-out")
+                .arg(pvt_key_path)
+                .spawn()?
+                .wait()?
+                .success();
+            assert!(pkcs8_key_generated);
+        }
+
+        if !Path::new(pub_key_path).exists() {
+            let pub_key_generated = Command::new("openssl")
+                .arg("ec")
+                .arg("-in")
+                .arg(pvt_key_path)
+                .arg("-pubout")
+                .arg("-out")
+                .arg(pub_key_path)
+                .spawn()?
+                .wait()?
+                .success();
+            assert!(pub_key_generated);
+        }
+
+        Ok((fs::read_to_string(pvt_key_path)?, fs::read_to_string(pub_key_path)?))
+    }
+
+    #[test]
+    fn generate_and_verify_token() -> Result<(), anyhow::Error> {
+        let (encrypted_pvt_key, pub_key) = rsa_keypair_pems_at(EXPIRY_PVT_KEY_PATH, EXPIRY_PUB_KEY_PATH)?;
 
         let jwt_helper: JWTHelper = JWTHelper::builder()
+            .algorithm(Algorithm::RS256)
             .pvt_key_secret("testpassword")
             .expiry_secs(2)
             .leeway(0)
             .encrypted_pvt_key(encrypted_pvt_key)
             .pub_key(RsaPublicKey::from_public_key_pem(&pub_key)?)
-            .build();
+            .now(expiry_test_now)
+            .build()?;
 
         let user_id = "user_123";
         let signed_token = jwt_helper.generate_token(user_id.to_string())?;
@@ -146,10 +411,205 @@ mod tests {
 
         assert_eq!(user_id, decoded_user_id);
 
-        // Sleep for longer than this token is valid for, and then try validating token, it should fail.
-        thread::sleep(Duration::from_secs(3));
+        // Move the injected clock past this token's expiry instead of sleeping for real.
+        EXPIRY_TEST_CLOCK_OFFSET_SECS.store(3, Ordering::SeqCst);
+        assert!(jwt_helper.validate_token(&signed_token).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn nbf_rejects_token_before_not_before_time() -> Result<(), anyhow::Error> {
+        let (encrypted_pvt_key, pub_key) = rsa_keypair_pems_at(NBF_PVT_KEY_PATH, NBF_PUB_KEY_PATH)?;
+
+        let jwt_helper: JWTHelper = JWTHelper::builder()
+            .algorithm(Algorithm::RS256)
+            .pvt_key_secret("testpassword")
+            .expiry_secs(60)
+            .leeway(0)
+            .encrypted_pvt_key(encrypted_pvt_key)
+            .pub_key(RsaPublicKey::from_public_key_pem(&pub_key)?)
+            .nbf_offset_secs(5)
+            .now(nbf_test_now)
+            .build()?;
+
+        let signed_token = jwt_helper.generate_token("user_123".to_string())?;
+
+        // Not yet valid: "now" is still before `iat + nbf_offset_secs`.
         assert!(jwt_helper.validate_token(&signed_token).is_err());
 
+        // Move the injected clock past the `nbf` boundary.
+        NBF_TEST_CLOCK_OFFSET_SECS.store(5, Ordering::SeqCst);
+        assert!(jwt_helper.validate_token(&signed_token).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_token_picks_matching_key_by_kid() -> Result<(), anyhow::Error> {
+        let (primary_encrypted_pvt_key, primary_pub_key) =
+            rsa_keypair_pems_at(KID_PRIMARY_PVT_KEY_PATH, KID_PRIMARY_PUB_KEY_PATH)?;
+        let (rotated_encrypted_pvt_key, rotated_pub_key) =
+            rsa_keypair_pems_at(ROTATED_PVT_KEY_PATH, ROTATED_PUB_KEY_PATH)?;
+
+        // Signs with the rotated key, stamping its `kid` in the header.
+        let signer: JWTHelper = JWTHelper::builder()
+            .algorithm(Algorithm::RS256)
+            .pvt_key_secret("testpassword")
+            .expiry_secs(60)
+            .leeway(0)
+            .encrypted_pvt_key(rotated_encrypted_pvt_key)
+            .pub_key(RsaPublicKey::from_public_key_pem(&rotated_pub_key)?)
+            .key_id("v2")
+            .build()?;
+
+        // Knows about both the old primary key and the rotated "v2" key.
+        let verifier: JWTHelper = JWTHelper::builder()
+            .algorithm(Algorithm::RS256)
+            .pvt_key_secret("testpassword")
+            .expiry_secs(60)
+            .leeway(0)
+            .encrypted_pvt_key(primary_encrypted_pvt_key)
+            .pub_key(RsaPublicKey::from_public_key_pem(&primary_pub_key)?)
+            .verification_key("v2", RsaPublicKey::from_public_key_pem(&rotated_pub_key)?)
+            .build()?;
+
+        let signed_token = signer.generate_token("user_123".to_string())?;
+
+        assert_eq!("user_123", verifier.validate_token(&signed_token)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn generate_and_verify_token_hmac() -> Result<(), anyhow::Error> {
+        let jwt_helper: JWTHelper = JWTHelper::builder()
+            .algorithm(Algorithm::HS256)
+            .expiry_secs(60)
+            .leeway(0)
+            .secret("shared-test-secret".to_string())
+            .build()?;
+
+        let user_id = "user_123";
+        let signed_token = jwt_helper.generate_token(user_id.to_string())?;
+
+        assert_eq!(user_id, jwt_helper.validate_token(&signed_token)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn generate_and_verify_token_ecdsa() -> Result<(), anyhow::Error> {
+        let (ec_pvt_key, ec_pub_key) = ec_keypair_pems_at(EC_PVT_KEY_PATH, EC_PUB_KEY_PATH)?;
+
+        let jwt_helper: JWTHelper = JWTHelper::builder()
+            .algorithm(Algorithm::ES256)
+            .expiry_secs(60)
+            .leeway(0)
+            .ec_pvt_key(ec_pvt_key)
+            .ec_pub_key(ec_pub_key)
+            .build()?;
+
+        let user_id = "user_123";
+        let signed_token = jwt_helper.generate_token(user_id.to_string())?;
+
+        assert_eq!(user_id, jwt_helper.validate_token(&signed_token)?);
+
+        Ok(())
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct CustomClaims {
+        sub: String,
+        role: String,
+        tenant_id: String,
+    }
+
+    #[test]
+    fn generate_and_verify_token_with_custom_claims() -> Result<(), anyhow::Error> {
+        let jwt_helper: JWTHelper = JWTHelper::builder()
+            .algorithm(Algorithm::HS256)
+            .expiry_secs(60)
+            .leeway(0)
+            .secret("shared-test-secret".to_string())
+            .build()?;
+
+        let claims = CustomClaims {
+            sub: "user_123".to_string(),
+            role: "admin".to_string(),
+            tenant_id: "tenant_1".to_string(),
+        };
+
+        let signed_token = jwt_helper.generate_token_with_claims(&claims)?;
+
+        // The auto-injected `exp`/`iat` aren't part of `CustomClaims`, so a successful round-trip
+        // here also proves they don't clobber the caller's fields.
+        let decoded_claims: CustomClaims = jwt_helper.validate_token_into(&signed_token)?;
+
+        assert_eq!(claims, decoded_claims);
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_token_accepts_and_rejects_audience_any_of() -> Result<(), anyhow::Error> {
+        let signer: JWTHelper = JWTHelper::builder()
+            .algorithm(Algorithm::HS256)
+            .expiry_secs(60)
+            .leeway(0)
+            .secret("shared-test-secret".to_string())
+            .audience("service-a".to_string())
+            .audience("service-b".to_string())
+            .build()?;
+
+        let signed_token = signer.generate_token("user_123".to_string())?;
+
+        // Shares "service-b" with the token's `aud`, so it's accepted.
+        let overlapping_verifier: JWTHelper = JWTHelper::builder()
+            .algorithm(Algorithm::HS256)
+            .expiry_secs(60)
+            .leeway(0)
+            .secret("shared-test-secret".to_string())
+            .audience("service-b".to_string())
+            .audience("service-c".to_string())
+            .build()?;
+        assert!(overlapping_verifier.validate_token(&signed_token).is_ok());
+
+        // Shares nothing with the token's `aud`, so it's rejected.
+        let non_overlapping_verifier: JWTHelper = JWTHelper::builder()
+            .algorithm(Algorithm::HS256)
+            .expiry_secs(60)
+            .leeway(0)
+            .secret("shared-test-secret".to_string())
+            .audience("service-x".to_string())
+            .build()?;
+        assert!(non_overlapping_verifier.validate_token(&signed_token).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn validate_token_rejects_issuer_mismatch() -> Result<(), anyhow::Error> {
+        let signer: JWTHelper = JWTHelper::builder()
+            .algorithm(Algorithm::HS256)
+            .expiry_secs(60)
+            .leeway(0)
+            .secret("shared-test-secret".to_string())
+            .issuer("issuer-a".to_string())
+            .build()?;
+
+        let verifier: JWTHelper = JWTHelper::builder()
+            .algorithm(Algorithm::HS256)
+            .expiry_secs(60)
+            .leeway(0)
+            .secret("shared-test-secret".to_string())
+            .issuer("issuer-b".to_string())
+            .build()?;
+
+        let signed_token = signer.generate_token("user_123".to_string())?;
+
+        assert!(verifier.validate_token(&signed_token).is_err());
+
         Ok(())
     }
 }